@@ -0,0 +1,76 @@
+use core::pin::Pin;
+use futures_core::future::Future;
+use futures_core::task::{LocalWaker, Poll};
+use pin_utils::unsafe_pinned;
+
+/// Future for the [`poll_immediate()`] function.
+///
+/// Unlike most futures, this future always resolves the first time it's
+/// polled: its output is the `Poll` the inner future produced, rather than
+/// the inner future's own output. This lets a driver observe readiness
+/// non-destructively, instead of suspending, and make scheduling decisions
+/// accordingly.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct PollImmediate<Fut> {
+    future: Option<Fut>,
+}
+
+impl<Fut> PollImmediate<Fut> {
+    unsafe_pinned!(future: Option<Fut>);
+
+    pub(super) fn new(future: Fut) -> PollImmediate<Fut> {
+        PollImmediate { future: Some(future) }
+    }
+}
+
+impl<Fut: Future> Future for PollImmediate<Fut> {
+    type Output = Poll<Fut::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Self::Output> {
+        let inner = self.future().as_pin_mut()
+            .expect("PollImmediate polled after completion");
+
+        match inner.poll(lw) {
+            Poll::Ready(t) => {
+                self.future().set(None);
+                Poll::Ready(Poll::Ready(t))
+            }
+            Poll::Pending => Poll::Ready(Poll::Pending),
+        }
+    }
+}
+
+/// Polls `future` once, resolving immediately to the resulting `Poll`
+/// instead of suspending when the inner future isn't ready yet.
+///
+/// This lets code observe whether a future is ready without blocking on
+/// it, replacing the ad-hoc `match poll_res { Poll::Pending => None, .. }`
+/// pattern duplicated at call sites that need that distinction.
+pub fn poll_immediate<Fut: Future>(future: Fut) -> PollImmediate<Fut> {
+    PollImmediate::new(future)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::poll_immediate;
+    use crate::future;
+    use core::pin::Pin;
+    use futures_core::future::Future;
+    use futures_core::task::Poll;
+    use futures_test::task::new_count_waker;
+
+    #[test]
+    fn resolves_to_ready_when_the_inner_future_is_ready() {
+        let (waker, _count) = new_count_waker();
+        let mut fut = poll_immediate(future::ready(5));
+        assert_eq!(Pin::new(&mut fut).poll(&waker), Poll::Ready(Poll::Ready(5)));
+    }
+
+    #[test]
+    fn resolves_to_pending_when_the_inner_future_is_pending() {
+        let (waker, _count) = new_count_waker();
+        let mut fut = poll_immediate(future::pending::<i32>());
+        assert_eq!(Pin::new(&mut fut).poll(&waker), Poll::Ready(Poll::Pending));
+    }
+}
@@ -0,0 +1,77 @@
+use core::pin::Pin;
+use futures_core::stream::Stream;
+use futures_core::task::{LocalWaker, Poll};
+use pin_utils::unsafe_pinned;
+
+/// Stream for the [`poll_immediate()`] function.
+///
+/// Each item is the `Poll` the inner stream produced for that call,
+/// letting a driver see a `Pending` inner stream without being suspended
+/// itself.
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct PollImmediate<St> {
+    stream: Option<St>,
+}
+
+impl<St> PollImmediate<St> {
+    unsafe_pinned!(stream: Option<St>);
+
+    pub(super) fn new(stream: St) -> PollImmediate<St> {
+        PollImmediate { stream: Some(stream) }
+    }
+}
+
+impl<St: Stream> Stream for PollImmediate<St> {
+    type Item = Poll<St::Item>;
+
+    fn poll_next(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Option<Self::Item>> {
+        let stream = match self.stream().as_pin_mut() {
+            Some(stream) => stream,
+            None => return Poll::Ready(None),
+        };
+
+        match stream.poll_next(lw) {
+            Poll::Ready(Some(item)) => Poll::Ready(Some(Poll::Ready(item))),
+            Poll::Ready(None) => {
+                self.stream().set(None);
+                Poll::Ready(None)
+            }
+            Poll::Pending => Poll::Ready(Some(Poll::Pending)),
+        }
+    }
+}
+
+/// Wraps `stream` so that every call to `poll_next` yields the `Poll` the
+/// inner stream produced instead of suspending when it isn't ready yet.
+///
+/// This lets a driver try a stream without blocking on it, e.g. to make
+/// scheduling decisions among several sources.
+pub fn poll_immediate<St: Stream>(stream: St) -> PollImmediate<St> {
+    PollImmediate::new(stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::poll_immediate;
+    use crate::test_utils::Script;
+    use alloc::collections::VecDeque;
+    use core::pin::Pin;
+    use futures_core::stream::Stream;
+    use futures_core::task::Poll;
+    use futures_test::task::new_count_waker;
+
+    #[test]
+    fn yields_pending_then_ready_then_ends() {
+        let (waker, _count) = new_count_waker();
+        let mut steps = VecDeque::new();
+        steps.push_back(Poll::Pending);
+        steps.push_back(Poll::Ready(Some(1)));
+        steps.push_back(Poll::Ready(None));
+        let mut s = poll_immediate(Script { steps });
+
+        assert_eq!(Pin::new(&mut s).poll_next(&waker), Poll::Ready(Some(Poll::Pending)));
+        assert_eq!(Pin::new(&mut s).poll_next(&waker), Poll::Ready(Some(Poll::Ready(1))));
+        assert_eq!(Pin::new(&mut s).poll_next(&waker), Poll::Ready(None));
+    }
+}
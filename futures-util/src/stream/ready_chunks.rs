@@ -0,0 +1,115 @@
+use crate::stream::{Fuse, StreamExt};
+use alloc::vec::Vec;
+use core::mem;
+use core::pin::Pin;
+use futures_core::stream::Stream;
+use futures_core::task::{LocalWaker, Poll};
+use pin_utils::{unsafe_pinned, unsafe_unpinned};
+
+/// An adaptor that batches every item the inner stream has ready
+/// *synchronously* into a single `Vec`, without ever introducing a delay
+/// of its own.
+///
+/// This structure is returned by the
+/// [`StreamExt::ready_chunks`](super::StreamExt::ready_chunks) method.
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct ReadyChunks<St: Stream> {
+    stream: Fuse<St>,
+    items: Vec<St::Item>,
+    cap: usize,
+}
+
+impl<St: Stream> Unpin for ReadyChunks<St>
+    where St: Unpin,
+{}
+
+impl<St: Stream> ReadyChunks<St> {
+    unsafe_pinned!(stream: Fuse<St>);
+    unsafe_unpinned!(items: Vec<St::Item>);
+    unsafe_unpinned!(cap: usize);
+
+    pub(super) fn new(stream: St, capacity: usize) -> ReadyChunks<St> {
+        assert!(capacity > 0);
+
+        ReadyChunks {
+            stream: stream.fuse(),
+            items: Vec::with_capacity(capacity),
+            cap: capacity,
+        }
+    }
+}
+
+impl<St: Stream> Stream for ReadyChunks<St> {
+    type Item = Vec<St::Item>;
+
+    fn poll_next(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Option<Self::Item>> {
+        loop {
+            match self.stream().poll_next(lw) {
+                Poll::Ready(Some(item)) => {
+                    self.items().push(item);
+                    if self.items().len() >= *self.cap() {
+                        return Poll::Ready(Some(mem::replace(self.items(), Vec::new())));
+                    }
+                }
+                Poll::Ready(None) => {
+                    return if self.items().is_empty() {
+                        Poll::Ready(None)
+                    } else {
+                        Poll::Ready(Some(mem::replace(self.items(), Vec::new())))
+                    };
+                }
+                Poll::Pending => {
+                    return if self.items().is_empty() {
+                        Poll::Pending
+                    } else {
+                        Poll::Ready(Some(mem::replace(self.items(), Vec::new())))
+                    };
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReadyChunks;
+    use crate::stream;
+    use crate::test_utils::Script;
+    use alloc::collections::VecDeque;
+    use alloc::vec;
+    use core::pin::Pin;
+    use futures_core::task::Poll;
+    use futures_test::task::new_count_waker;
+
+    #[test]
+    fn flushes_on_cap() {
+        let (waker, _count) = new_count_waker();
+        let mut chunks = ReadyChunks::new(stream::iter(vec![1, 2, 3, 4, 5]), 2);
+
+        assert_eq!(Pin::new(&mut chunks).poll_next(&waker), Poll::Ready(Some(vec![1, 2])));
+        assert_eq!(Pin::new(&mut chunks).poll_next(&waker), Poll::Ready(Some(vec![3, 4])));
+        assert_eq!(Pin::new(&mut chunks).poll_next(&waker), Poll::Ready(Some(vec![5])));
+        assert_eq!(Pin::new(&mut chunks).poll_next(&waker), Poll::Ready(None));
+    }
+
+    #[test]
+    fn flushes_on_pending_and_emits_final_partial_batch() {
+        let (waker, _count) = new_count_waker();
+        let mut steps = VecDeque::new();
+        steps.push_back(Poll::Ready(Some(1)));
+        steps.push_back(Poll::Ready(Some(2)));
+        steps.push_back(Poll::Pending);
+        steps.push_back(Poll::Ready(Some(3)));
+        steps.push_back(Poll::Ready(None));
+        let mut chunks = ReadyChunks::new(Script { steps }, 10);
+
+        // The inner stream goes Pending after two items: those two are
+        // emitted at once as a batch, not held back waiting to fill the cap.
+        assert_eq!(Pin::new(&mut chunks).poll_next(&waker), Poll::Ready(Some(vec![1, 2])));
+        // The stream then ends with one more item: emitted as a final
+        // partial batch, not held back waiting to fill the cap.
+        assert_eq!(Pin::new(&mut chunks).poll_next(&waker), Poll::Ready(Some(vec![3])));
+        assert_eq!(Pin::new(&mut chunks).poll_next(&waker), Poll::Ready(None));
+    }
+}
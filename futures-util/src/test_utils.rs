@@ -0,0 +1,25 @@
+//! Test-only helpers shared across this crate's unit tests.
+
+#![cfg(test)]
+
+use alloc::collections::VecDeque;
+use core::pin::Pin;
+use futures_core::stream::Stream;
+use futures_core::task::{LocalWaker, Poll};
+
+/// A stream that replays a fixed script of `poll_next` results, letting
+/// tests force an exact sequence of items, `Pending`s, and errors without
+/// reaching for a real I/O source.
+pub(crate) struct Script<T> {
+    pub(crate) steps: VecDeque<Poll<Option<T>>>,
+}
+
+impl<T> Unpin for Script<T> {}
+
+impl<T> Stream for Script<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, _lw: &LocalWaker) -> Poll<Option<T>> {
+        self.get_mut().steps.pop_front().expect("script exhausted")
+    }
+}
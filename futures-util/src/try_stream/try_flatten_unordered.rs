@@ -0,0 +1,253 @@
+use alloc::boxed::Box;
+use crate::stream::{FuturesUnordered, StreamExt};
+use core::mem;
+use core::marker::Unpin;
+use core::pin::Pin;
+use core::num::NonZeroUsize;
+use futures_core::future::Future;
+use futures_core::stream::TryStream;
+use futures_core::task::{LocalWaker, Poll};
+use pin_utils::{unsafe_pinned, unsafe_unpinned};
+
+/// A future which polls an inner stream for a single item and hands the
+/// stream back alongside the result, so the driving combinator can poll it
+/// again later without losing its place.
+///
+/// The stream is kept behind `Pin<Box<S>>` rather than bare `S` so that
+/// handing it back and pushing it into a fresh `StreamFuture` never moves
+/// `S` itself — only the box — which is what lets this combinator accept
+/// inner streams that aren't `Unpin`, matching `TryForEachConcurrent`'s
+/// unconstrained `FuturesUnordered<Fut>`.
+#[derive(Debug)]
+struct StreamFuture<S> {
+    stream: Option<Pin<Box<S>>>,
+}
+
+impl<S> Unpin for StreamFuture<S> {}
+
+impl<S> StreamFuture<S> {
+    fn new(stream: S) -> StreamFuture<S> {
+        StreamFuture { stream: Some(Box::pin(stream)) }
+    }
+
+    fn from_pinned(stream: Pin<Box<S>>) -> StreamFuture<S> {
+        StreamFuture { stream: Some(stream) }
+    }
+}
+
+impl<S> Future for StreamFuture<S>
+    where S: TryStream,
+{
+    type Output = (Option<Result<S::Ok, S::Error>>, Option<Pin<Box<S>>>);
+
+    fn poll(self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let mut stream = this.stream.take().expect("StreamFuture polled after completion");
+        match stream.as_mut().try_poll_next(lw) {
+            Poll::Ready(Some(item)) => Poll::Ready((Some(item), Some(stream))),
+            Poll::Ready(None) => Poll::Ready((None, None)),
+            Poll::Pending => {
+                this.stream = Some(stream);
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// A stream which concurrently flattens a stream of `TryStream`s, polling
+/// up to a limited number of the inner streams at once and yielding their
+/// items interleaved as they become ready.
+///
+/// This structure is returned by the
+/// [`TryStreamExt::try_flatten_unordered`](super::TryStreamExt::try_flatten_unordered)
+/// method.
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct TryFlattenUnordered<St>
+    where St: TryStream,
+{
+    stream: Option<St>,
+    inner_streams: FuturesUnordered<StreamFuture<St::Ok>>,
+    limit: Option<NonZeroUsize>,
+}
+
+impl<St> Unpin for TryFlattenUnordered<St>
+    where St: TryStream + Unpin,
+{}
+
+impl<St> TryFlattenUnordered<St>
+    where St: TryStream,
+          St::Ok: TryStream<Error = St::Error>,
+{
+    unsafe_pinned!(stream: Option<St>);
+    unsafe_unpinned!(inner_streams: FuturesUnordered<StreamFuture<St::Ok>>);
+    unsafe_unpinned!(limit: Option<NonZeroUsize>);
+
+    pub(super) fn new(stream: St, limit: Option<usize>) -> TryFlattenUnordered<St> {
+        TryFlattenUnordered {
+            stream: Some(stream),
+            // Note: `limit` = 0 gets ignored.
+            limit: limit.and_then(NonZeroUsize::new),
+            inner_streams: FuturesUnordered::new(),
+        }
+    }
+}
+
+impl<St> TryStream for TryFlattenUnordered<St>
+    where St: TryStream,
+          St::Ok: TryStream<Error = St::Error>,
+{
+    type Ok = <St::Ok as TryStream>::Ok;
+    type Error = St::Error;
+
+    fn try_poll_next(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Option<Result<Self::Ok, Self::Error>>> {
+        loop {
+            // Pull a new inner stream from the outer source if we're
+            // below the concurrency limit.
+            let current_len = self.inner_streams().len();
+            if self.limit().map(|limit| limit.get() > current_len).unwrap_or(true) {
+                let poll_res = match self.stream().as_pin_mut() {
+                    Some(stream) => stream.try_poll_next(lw),
+                    None => Poll::Ready(None),
+                };
+
+                match poll_res {
+                    Poll::Ready(Some(Ok(inner_stream))) => {
+                        self.inner_streams().push(StreamFuture::new(inner_stream));
+                        continue;
+                    }
+                    Poll::Ready(None) => {
+                        self.stream().set(None);
+                    }
+                    Poll::Pending => {}
+                    Poll::Ready(Some(Err(e))) => {
+                        // Drop the outer source and every active inner
+                        // stream so that we know the stream has completed.
+                        self.stream().set(None);
+                        drop(mem::replace(self.inner_streams(), FuturesUnordered::new()));
+                        return Poll::Ready(Some(Err(e)));
+                    }
+                }
+            }
+
+            match self.inner_streams().poll_next_unpin(lw) {
+                Poll::Ready(Some((Some(Ok(item)), Some(remaining)))) => {
+                    self.inner_streams().push(StreamFuture::from_pinned(remaining));
+                    return Poll::Ready(Some(Ok(item)));
+                }
+                Poll::Ready(Some((Some(Err(e)), _))) => {
+                    self.stream().set(None);
+                    drop(mem::replace(self.inner_streams(), FuturesUnordered::new()));
+                    return Poll::Ready(Some(Err(e)));
+                }
+                Poll::Ready(Some((None, _))) => {
+                    // That inner stream is exhausted; loop around to pull
+                    // another or poll the rest of the active set.
+                }
+                Poll::Ready(None) => {
+                    if self.stream().is_none() {
+                        return Poll::Ready(None);
+                    }
+                    return Poll::Pending;
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TryFlattenUnordered;
+    use crate::stream;
+    use alloc::vec::Vec;
+    use core::cell::Cell;
+    use core::pin::Pin;
+    use futures_core::stream::{Stream, TryStream};
+    use futures_core::task::{LocalWaker, Poll};
+    use futures_test::task::new_count_waker;
+
+    /// A stream that counts every poll and never resolves, used to observe
+    /// how many inner streams the combinator actually admitted.
+    struct PendingForever<'a> {
+        polled: &'a Cell<usize>,
+    }
+
+    impl<'a> Stream for PendingForever<'a> {
+        type Item = Result<i32, ()>;
+
+        fn poll_next(self: Pin<&mut Self>, _lw: &LocalWaker) -> Poll<Option<Self::Item>> {
+            self.polled.set(self.polled.get() + 1);
+            Poll::Pending
+        }
+    }
+
+    #[test]
+    fn limit_caps_the_number_of_concurrently_admitted_inner_streams() {
+        let (waker, _count) = new_count_waker();
+        let polled = Cell::new(0);
+        let polled_1 = &polled;
+        let polled_2 = &polled;
+        let polled_3 = &polled;
+
+        let outer = stream::iter(vec![
+            Ok::<_, ()>(PendingForever { polled: polled_1 }),
+            Ok(PendingForever { polled: polled_2 }),
+            Ok(PendingForever { polled: polled_3 }),
+        ]);
+        let mut flattened = TryFlattenUnordered::new(outer, Some(2));
+
+        assert_eq!(
+            Pin::new(&mut flattened).try_poll_next(&waker),
+            Poll::Pending,
+        );
+        // Only the two inner streams admitted under the cap get polled;
+        // the third is left in the outer source.
+        assert_eq!(polled.get(), 2);
+    }
+
+    #[test]
+    fn drains_every_item_from_every_inner_stream_unordered() {
+        let (waker, _count) = new_count_waker();
+        let outer = stream::iter(vec![
+            Ok::<_, ()>(stream::iter(vec![Ok(1), Ok(2)])),
+            Ok(stream::iter(vec![Ok(3), Ok(4)])),
+        ]);
+        let mut flattened = TryFlattenUnordered::new(outer, None);
+
+        let mut items = Vec::new();
+        loop {
+            match Pin::new(&mut flattened).try_poll_next(&waker) {
+                Poll::Ready(Some(Ok(item))) => items.push(item),
+                Poll::Ready(Some(Err(()))) => panic!("unexpected error"),
+                Poll::Ready(None) => break,
+                Poll::Pending => panic!("inner streams are always ready"),
+            }
+        }
+        items.sort();
+        assert_eq!(items, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn short_circuits_and_cleans_up_on_inner_error() {
+        let (waker, _count) = new_count_waker();
+        let outer = stream::iter(vec![Ok::<_, ()>(stream::iter(vec![Ok(1), Err(())]))]);
+        let mut flattened = TryFlattenUnordered::new(outer, None);
+
+        let mut items = Vec::new();
+        let err = loop {
+            match Pin::new(&mut flattened).try_poll_next(&waker) {
+                Poll::Ready(Some(Ok(item))) => items.push(item),
+                Poll::Ready(Some(Err(e))) => break e,
+                Poll::Ready(None) => panic!("expected an error before completion"),
+                Poll::Pending => panic!("inner stream is always ready"),
+            }
+        };
+
+        assert_eq!(items, vec![1]);
+        assert_eq!(err, ());
+        // Once the error has been surfaced, the whole combinator is done:
+        // the outer source and every inner stream were dropped already.
+        assert_eq!(Pin::new(&mut flattened).try_poll_next(&waker), Poll::Ready(None));
+    }
+}
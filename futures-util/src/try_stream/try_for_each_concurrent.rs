@@ -1,13 +1,66 @@
-use crate::stream::{FuturesUnordered, StreamExt};
+use alloc::sync::Arc;
+use crate::future::{AbortHandle, Abortable};
+use crate::stream::{ForEachConcurrent, FuturesUnordered, StreamExt};
+use crate::try_stream::{TryBufferUnordered, TryStreamExt};
 use core::mem;
 use core::marker::Unpin;
 use core::pin::Pin;
 use core::num::NonZeroUsize;
-use futures_core::future::{FusedFuture, Future};
-use futures_core::stream::TryStream;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use futures_core::future::{FusedFuture, Future, TryFuture};
+use futures_core::stream::{Stream, TryStream};
 use futures_core::task::{LocalWaker, Poll};
 use pin_utils::{unsafe_pinned, unsafe_unpinned};
 
+/// A cloneable, atomically-readable handle to a concurrency limit that can
+/// be raised or lowered at runtime.
+///
+/// Every clone shares the same underlying counter, so adjusting the limit
+/// through one clone is immediately visible to a
+/// [`TryForEachConcurrent`] driven by another — useful for adaptive
+/// backpressure that reacts to downstream latency or a connection pool's
+/// available slots.
+#[derive(Debug, Clone)]
+pub struct ConcurrencyLimit(Arc<AtomicUsize>);
+
+impl ConcurrencyLimit {
+    /// Creates a new handle initialized to `limit`. A limit of `0` means
+    /// unbounded concurrency, matching how `TryForEachConcurrent::new`
+    /// treats a `limit` of `None`.
+    pub fn new(limit: usize) -> ConcurrencyLimit {
+        ConcurrencyLimit(Arc::new(AtomicUsize::new(limit)))
+    }
+
+    /// Raises or lowers the limit. In-flight work above a newly-lowered
+    /// bound is never cancelled; admission of new work simply pauses
+    /// until the in-flight count drops back under the bound.
+    pub fn set(&self, limit: usize) {
+        self.0.store(limit, Ordering::SeqCst);
+    }
+
+    fn get(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+#[derive(Debug)]
+enum Limit {
+    Fixed(Option<NonZeroUsize>),
+    Shared(ConcurrencyLimit),
+}
+
+impl Limit {
+    fn current(&self) -> Option<usize> {
+        match self {
+            Limit::Fixed(limit) => limit.map(NonZeroUsize::get),
+            Limit::Shared(limit) => match limit.get() {
+                0 => None,
+                limit => Some(limit),
+            },
+        }
+    }
+}
+
 /// A stream combinator which executes a unit closure over each item on a
 /// stream concurrently.
 ///
@@ -20,7 +73,7 @@ pub struct TryForEachConcurrent<St, Fut, F> {
     stream: Option<St>,
     f: F,
     futures: FuturesUnordered<Fut>,
-    limit: Option<NonZeroUsize>,
+    limit: Limit,
 }
 
 impl<St, Fut, F> Unpin for TryForEachConcurrent<St, Fut, F>
@@ -42,17 +95,86 @@ where St: TryStream,
     unsafe_pinned!(stream: Option<St>);
     unsafe_unpinned!(f: F);
     unsafe_unpinned!(futures: FuturesUnordered<Fut>);
-    unsafe_unpinned!(limit: Option<NonZeroUsize>);
+    unsafe_unpinned!(limit: Limit);
 
     pub(super) fn new(stream: St, limit: Option<usize>, f: F) -> TryForEachConcurrent<St, Fut, F> {
         TryForEachConcurrent {
             stream: Some(stream),
             // Note: `limit` = 0 gets ignored.
-            limit: limit.and_then(NonZeroUsize::new),
+            limit: Limit::Fixed(limit.and_then(NonZeroUsize::new)),
+            f,
+            futures: FuturesUnordered::new(),
+        }
+    }
+
+    pub(super) fn new_with_shared_limit(
+        stream: St,
+        limit: ConcurrencyLimit,
+        f: F,
+    ) -> TryForEachConcurrent<St, Fut, F> {
+        TryForEachConcurrent {
+            stream: Some(stream),
+            limit: Limit::Shared(limit),
             f,
             futures: FuturesUnordered::new(),
         }
     }
+
+    /// Builds this future paired with an [`AbortHandle`], so that calling
+    /// [`AbortHandle::abort`] causes the very next `poll` to resolve to
+    /// `Err(Aborted)` without polling the source stream or any in-flight
+    /// future again, rather than running every spawned future to
+    /// completion. The abort does not go through the error cleanup path:
+    /// the source stream and the in-flight futures are not dropped at that
+    /// point, but stay inside the returned [`Abortable`] until it is dropped.
+    ///
+    /// See [`for_each_concurrent_abortable`] and
+    /// [`try_buffer_unordered_abortable`] for the same treatment applied to
+    /// this combinator's siblings.
+    pub(super) fn new_abortable(
+        stream: St,
+        limit: Option<usize>,
+        f: F,
+    ) -> (Abortable<TryForEachConcurrent<St, Fut, F>>, AbortHandle) {
+        let (handle, registration) = AbortHandle::new_pair();
+        (Abortable::new(Self::new(stream, limit, f), registration), handle)
+    }
+}
+
+/// Pairs `stream.for_each_concurrent(limit, f)` with an [`AbortHandle`],
+/// using the same [`Abortable`]-based cancellation as
+/// [`TryForEachConcurrent::new_abortable`]: after an abort, the next poll
+/// resolves to `Err(Aborted)` without polling the stream or any in-flight
+/// future again. The stream and the in-flight futures are dropped only
+/// when the returned [`Abortable`] is dropped.
+///
+/// This wraps the existing [`StreamExt::for_each_concurrent`] rather than
+/// reimplementing `ForEachConcurrent`, so it needs no access to that
+/// combinator's internals.
+pub(super) fn for_each_concurrent_abortable<St, Fut, F>(
+    stream: St,
+    limit: impl Into<Option<usize>>,
+    f: F,
+) -> (Abortable<ForEachConcurrent<St, Fut, F>>, AbortHandle)
+    where St: Stream,
+          F: FnMut(St::Item) -> Fut,
+          Fut: Future<Output = ()>,
+{
+    let (handle, registration) = AbortHandle::new_pair();
+    (Abortable::new(stream.for_each_concurrent(limit, f), registration), handle)
+}
+
+/// Pairs `stream.try_buffer_unordered(limit)` with an [`AbortHandle`], the
+/// same way [`for_each_concurrent_abortable`] wraps `for_each_concurrent`.
+pub(super) fn try_buffer_unordered_abortable<St>(
+    stream: St,
+    limit: impl Into<Option<usize>>,
+) -> (Abortable<TryBufferUnordered<St>>, AbortHandle)
+    where St: TryStream,
+          St::Ok: TryFuture<Error = St::Error>,
+{
+    let (handle, registration) = AbortHandle::new_pair();
+    (Abortable::new(stream.try_buffer_unordered(limit), registration), handle)
 }
 
 impl<St, Fut, F> Future for TryForEachConcurrent<St, Fut, F>
@@ -68,8 +190,10 @@ impl<St, Fut, F> Future for TryForEachConcurrent<St, Fut, F>
 
             // Try and pull an item from the stream
             let current_len = self.futures().len();
-            // Check if we've already created a number of futures greater than `limit`
-            if self.limit().map(|limit| limit.get() > current_len).unwrap_or(true) {
+            // Check if we've already created a number of futures greater than `limit`.
+            // The limit is re-read on every iteration so a cap raised or lowered while
+            // this future is running takes effect immediately.
+            if self.limit().current().map(|limit| limit > current_len).unwrap_or(true) {
                 let poll_res = match self.stream().as_pin_mut() {
                     Some(stream) => stream.try_poll_next(lw),
                     None => Poll::Ready(None),
@@ -123,3 +247,96 @@ impl<St, Fut, F> Future for TryForEachConcurrent<St, Fut, F>
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{for_each_concurrent_abortable, try_buffer_unordered_abortable, ConcurrencyLimit, TryForEachConcurrent};
+    use crate::future::{self, Aborted};
+    use crate::stream;
+    use core::cell::Cell;
+    use core::pin::Pin;
+    use futures_core::future::Future;
+    use futures_core::stream::Stream;
+    use futures_core::task::Poll;
+    use futures_test::task::new_count_waker;
+
+    #[test]
+    fn shared_limit_is_reread_on_every_poll() {
+        let limit = ConcurrencyLimit::new(1);
+        let started = Cell::new(0usize);
+        let (waker, _count) = new_count_waker();
+
+        let mut fut = TryForEachConcurrent::new_with_shared_limit(
+            stream::iter(vec![Ok::<(), ()>(()), Ok(()), Ok(())]),
+            limit.clone(),
+            |()| {
+                started.set(started.get() + 1);
+                future::pending::<Result<(), ()>>()
+            },
+        );
+
+        // With the cap at 1, only the first item's future is admitted.
+        assert_eq!(Pin::new(&mut fut).poll(&waker), Poll::Pending);
+        assert_eq!(started.get(), 1);
+
+        // Raising the cap admits the rest on the next poll.
+        limit.set(3);
+        assert_eq!(Pin::new(&mut fut).poll(&waker), Poll::Pending);
+        assert_eq!(started.get(), 3);
+
+        // Lowering the cap never cancels work already in flight.
+        limit.set(1);
+        assert_eq!(Pin::new(&mut fut).poll(&waker), Poll::Pending);
+        assert_eq!(started.get(), 3);
+        assert_eq!(fut.futures.len(), 3);
+    }
+
+    #[test]
+    fn abort_short_circuits_in_flight_work() {
+        let (waker, _count) = new_count_waker();
+
+        let (mut fut, handle) = TryForEachConcurrent::new_abortable(
+            stream::iter(vec![Ok::<(), ()>(()), Ok(())]),
+            None,
+            |()| future::pending::<Result<(), ()>>(),
+        );
+
+        assert_eq!(Pin::new(&mut fut).poll(&waker), Poll::Pending);
+
+        handle.abort();
+        assert_eq!(Pin::new(&mut fut).poll(&waker), Poll::Ready(Err(Aborted)));
+    }
+
+    #[test]
+    fn for_each_concurrent_abort_short_circuits_in_flight_work() {
+        let (waker, _count) = new_count_waker();
+
+        let (mut fut, handle) = for_each_concurrent_abortable(
+            stream::iter(vec![(), ()]),
+            None,
+            |()| future::pending::<()>(),
+        );
+
+        assert_eq!(Pin::new(&mut fut).poll(&waker), Poll::Pending);
+
+        handle.abort();
+        assert_eq!(Pin::new(&mut fut).poll(&waker), Poll::Ready(Err(Aborted)));
+    }
+
+    #[test]
+    fn try_buffer_unordered_abort_short_circuits_in_flight_work() {
+        let (waker, _count) = new_count_waker();
+
+        let (mut stream, handle) = try_buffer_unordered_abortable(
+            stream::iter(vec![future::pending::<Result<(), ()>>(), future::pending::<Result<(), ()>>()]),
+            None,
+        );
+
+        assert_eq!(Pin::new(&mut stream).poll_next(&waker), Poll::Pending);
+
+        // Aborting a stream ends it early rather than producing an error
+        // item, matching `Abortable`'s `Stream` impl.
+        handle.abort();
+        assert_eq!(Pin::new(&mut stream).poll_next(&waker), Poll::Ready(None));
+    }
+}
@@ -0,0 +1,134 @@
+use crate::stream::{Fuse, StreamExt};
+use alloc::vec::Vec;
+use core::mem;
+use core::pin::Pin;
+use futures_core::stream::TryStream;
+use futures_core::task::{LocalWaker, Poll};
+use pin_utils::{unsafe_pinned, unsafe_unpinned};
+
+/// An adaptor that batches every item the inner `TryStream` has ready
+/// *synchronously* into a single `Vec`, surfacing a mid-batch error only
+/// after the items collected before it have been emitted.
+///
+/// This structure is returned by the
+/// [`TryStreamExt::try_ready_chunks`](super::TryStreamExt::try_ready_chunks)
+/// method.
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct TryReadyChunks<St: TryStream> {
+    stream: Fuse<St>,
+    items: Vec<St::Ok>,
+    cap: usize,
+    error: Option<St::Error>,
+}
+
+impl<St: TryStream> Unpin for TryReadyChunks<St>
+    where St: Unpin,
+{}
+
+impl<St: TryStream> TryReadyChunks<St> {
+    unsafe_pinned!(stream: Fuse<St>);
+    unsafe_unpinned!(items: Vec<St::Ok>);
+    unsafe_unpinned!(cap: usize);
+    unsafe_unpinned!(error: Option<St::Error>);
+
+    pub(super) fn new(stream: St, capacity: usize) -> TryReadyChunks<St> {
+        assert!(capacity > 0);
+
+        TryReadyChunks {
+            stream: stream.fuse(),
+            items: Vec::with_capacity(capacity),
+            cap: capacity,
+            error: None,
+        }
+    }
+}
+
+impl<St: TryStream> TryStream for TryReadyChunks<St> {
+    type Ok = Vec<St::Ok>;
+    type Error = St::Error;
+
+    fn try_poll_next(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Option<Result<Self::Ok, Self::Error>>> {
+        if let Some(e) = self.error().take() {
+            return Poll::Ready(Some(Err(e)));
+        }
+
+        loop {
+            match self.stream().try_poll_next(lw) {
+                Poll::Ready(Some(Ok(item))) => {
+                    self.items().push(item);
+                    if self.items().len() >= *self.cap() {
+                        return Poll::Ready(Some(Ok(mem::replace(self.items(), Vec::new()))));
+                    }
+                }
+                Poll::Ready(Some(Err(e))) => {
+                    return if self.items().is_empty() {
+                        Poll::Ready(Some(Err(e)))
+                    } else {
+                        *self.error() = Some(e);
+                        Poll::Ready(Some(Ok(mem::replace(self.items(), Vec::new()))))
+                    };
+                }
+                Poll::Ready(None) => {
+                    return if self.items().is_empty() {
+                        Poll::Ready(None)
+                    } else {
+                        Poll::Ready(Some(Ok(mem::replace(self.items(), Vec::new()))))
+                    };
+                }
+                Poll::Pending => {
+                    return if self.items().is_empty() {
+                        Poll::Pending
+                    } else {
+                        Poll::Ready(Some(Ok(mem::replace(self.items(), Vec::new()))))
+                    };
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TryReadyChunks;
+    use crate::stream;
+    use crate::test_utils::Script;
+    use alloc::collections::VecDeque;
+    use alloc::vec;
+    use core::pin::Pin;
+    use futures_core::stream::TryStream;
+    use futures_core::task::Poll;
+    use futures_test::task::new_count_waker;
+
+    #[test]
+    fn flushes_on_cap() {
+        let (waker, _count) = new_count_waker();
+        let mut chunks = TryReadyChunks::new(
+            stream::iter(vec![Ok::<_, ()>(1), Ok(2), Ok(3), Ok(4), Ok(5)]),
+            2,
+        );
+
+        assert_eq!(Pin::new(&mut chunks).try_poll_next(&waker), Poll::Ready(Some(Ok(vec![1, 2]))));
+        assert_eq!(Pin::new(&mut chunks).try_poll_next(&waker), Poll::Ready(Some(Ok(vec![3, 4]))));
+        assert_eq!(Pin::new(&mut chunks).try_poll_next(&waker), Poll::Ready(Some(Ok(vec![5]))));
+        assert_eq!(Pin::new(&mut chunks).try_poll_next(&waker), Poll::Ready(None));
+    }
+
+    #[test]
+    fn drains_collected_items_before_surfacing_a_mid_batch_error() {
+        let (waker, _count) = new_count_waker();
+        let mut steps = VecDeque::new();
+        steps.push_back(Poll::Ready(Some(Ok(1))));
+        steps.push_back(Poll::Ready(Some(Ok(2))));
+        steps.push_back(Poll::Ready(Some(Err(()))));
+        steps.push_back(Poll::Ready(None));
+        let mut chunks = TryReadyChunks::new(Script { steps }, 10);
+
+        // The error arrives mid-batch: the items collected before it are
+        // emitted first...
+        assert_eq!(Pin::new(&mut chunks).try_poll_next(&waker), Poll::Ready(Some(Ok(vec![1, 2]))));
+        // ...and the error itself is only surfaced on the following poll.
+        assert_eq!(Pin::new(&mut chunks).try_poll_next(&waker), Poll::Ready(Some(Err(()))));
+        assert_eq!(Pin::new(&mut chunks).try_poll_next(&waker), Poll::Ready(None));
+    }
+}